@@ -14,7 +14,7 @@ impl Error {
 }
 
 impl Fail for Error {
-	fn cause(&self) -> Option<&Fail> {
+	fn cause(&self) -> Option<&dyn Fail> {
 		self.inner.cause()
 	}
 
@@ -47,4 +47,7 @@ impl From<Context<ErrorKind>> for Error {
 pub enum ErrorKind {
 	#[fail(display = "timer error: {}", _0)]
 	Timer(&'static str),
+
+	#[fail(display = "timed out waiting for an available throttle slot")]
+	Timeout,
 }
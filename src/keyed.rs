@@ -0,0 +1,54 @@
+use super::{HoldHandle, ThrottlePool, ThrottleRate};
+use futures::Future;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+
+/// A clonable object which is used to throttle many independent groups of items, according to a
+/// shared rate, where each group is identified by a key.
+///
+/// This is useful when you want to rate-limit requests per-destination (e.g. per host), without
+/// having to construct and track a separate `ThrottlePool` for each destination yourself. A
+/// `ThrottlePool` is lazily created the first time a given key is queued, and reused for every
+/// subsequent queue of that key.
+#[derive(Clone)]
+pub struct KeyedThrottlePool<K> {
+	inner: Arc<KeyedThrottlePoolInner<K>>,
+}
+
+impl<K: Hash + Eq + Clone> KeyedThrottlePool<K> {
+	pub fn new(rate: ThrottleRate) -> Self {
+		Self {
+			inner: Arc::new(KeyedThrottlePoolInner {
+				rate,
+				pools: Mutex::new(HashMap::new()),
+			}),
+		}
+	}
+
+	/// Produces a future which will resolve once the pool for `key` has an available slot.
+	pub fn queue(&self, key: K) -> impl Future<Output = ()> {
+		self.pool_for(key).queue()
+	}
+
+	/// Similar to queue(), but also returns a handle that will "hold" the slot until released.
+	pub fn queue_with_hold(&self, key: K) -> impl Future<Output = HoldHandle> {
+		self.pool_for(key).queue_with_hold()
+	}
+
+	// returns the ThrottlePool for `key`, creating it (sharing this pool's rate) if necessary
+	fn pool_for(&self, key: K) -> ThrottlePool {
+		self.inner
+			.pools
+			.lock()
+			.expect("poisoned mutex")
+			.entry(key)
+			.or_insert_with(|| ThrottlePool::new(self.inner.rate))
+			.clone()
+	}
+}
+
+struct KeyedThrottlePoolInner<K> {
+	rate: ThrottleRate,
+	pools: Mutex<HashMap<K, ThrottlePool>>,
+}
@@ -6,8 +6,8 @@
 //! - Throttling is implemented via
 //! [`poll()`](../futures/future/trait.Future.html#tymethod.poll), and not via any sort of
 //! buffering.
-//! - The throttling behaviour can be applied to both `Stream`'s and `Future`'s.
-//! - Multiple streams/futures can be throttled together as a group.
+//! - The throttling behaviour can be applied to `Stream`'s, `Sink`'s, and `Future`'s.
+//! - Multiple streams/sinks/futures can be throttled together as a group.
 //! - Feature flags to use various timer implementations.
 //!
 //! ## Feature Flags
@@ -50,10 +50,16 @@
 //! futures::executor::block_on(work);
 //! ```
 
+mod error;
+mod keyed;
 mod pool;
 mod rate;
+mod sink;
 mod stream;
 
+pub use error::*;
+pub use keyed::*;
 pub use pool::*;
 pub use rate::*;
+pub use sink::*;
 pub use stream::*;
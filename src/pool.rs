@@ -1,7 +1,9 @@
-use super::ThrottleRate;
+use super::{Error, ErrorKind, ThrottleRate};
 use futures::channel::oneshot::{Receiver, Sender};
+use futures::future::Either;
 use futures::Future;
 use log::{log_enabled, trace};
+use rand::Rng;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
@@ -23,12 +25,57 @@ impl ThrottlePool {
 
 		Self {
 			inner: Arc::new(ThrottlePoolInner {
-				rate_duration: rate.duration(),
-				slots,
+				kind: ThrottlePoolKind::Slotted(SlottedPool {
+					rate_duration: rate.duration(),
+					slots,
+				}),
+				jitter: None,
 			}),
 		}
 	}
 
+	/// Creates a pool backed by the Generic Cell Rate Algorithm (GCRA), rather than the fixed-slot
+	/// model used by `new()`.
+	///
+	/// Where `new()` spreads permits evenly across `rate.duration()`, this allows up to `burst`
+	/// items to be issued immediately, with the steady `rate` resuming once the burst is
+	/// exhausted. `burst` must be at least 1; a `burst` of 1 behaves like a plain, un-bursty rate
+	/// limit.
+	pub fn new_gcra(rate: ThrottleRate, burst: usize) -> Self {
+		assert!(burst > 0);
+
+		let emission_interval = rate.duration() / rate.count() as u32;
+		let tau = emission_interval * (burst - 1) as u32;
+
+		Self {
+			inner: Arc::new(ThrottlePoolInner {
+				kind: ThrottlePoolKind::Gcra(GcraPool {
+					emission_interval,
+					tau,
+					tat: Mutex::new(Instant::now()),
+				}),
+				jitter: None,
+			}),
+		}
+	}
+
+	/// Adds jitter to the delays this pool computes, so that many `Throttled` streams sharing one
+	/// pool don't all wake up on the same boundary and stampede the next free slot.
+	///
+	/// Each time the pool would otherwise sleep for `sleep`, it instead sleeps for
+	/// `sleep + min + rand(0..=interval)`. This spreads wake-ups out without changing the
+	/// long-run rate.
+	///
+	/// Must be called before the pool is cloned, since the jitter config is stored alongside the
+	/// pool's shared state.
+	pub fn with_jitter(mut self, min: Duration, interval: Duration) -> Self {
+		Arc::get_mut(&mut self.inner)
+			.expect("with_jitter() must be called before the pool is cloned")
+			.jitter = Some(Jitter { min, interval });
+
+		self
+	}
+
 	/// Produces a future which will resolve once the pool has an available slot.
 	///
 	/// Each `Throttled` stream will call this method during polling, once for each item the
@@ -43,63 +90,121 @@ impl ThrottlePool {
 		}
 	}
 
+	/// Similar to queue_with_hold(), but gives up and resolves to `ErrorKind::Timeout` if no slot
+	/// becomes available within `timeout`.
+	///
+	/// This is useful in request-handling paths that must fail fast rather than queue
+	/// unboundedly when the pool is saturated.
+	pub fn queue_with_timeout(
+		&self,
+		timeout: Duration,
+	) -> impl Future<Output = Result<HoldHandle, Error>> {
+		let queue = self.queue_with_hold();
+		async move {
+			let delay = delay_for(timeout);
+			futures::pin_mut!(queue);
+			futures::pin_mut!(delay);
+
+			match futures::future::select(queue, delay).await {
+				Either::Left((handle, _)) => Ok(handle),
+				Either::Right((_, _)) => Err(ErrorKind::Timeout.into()),
+			}
+		}
+	}
+
 	/// Similar to queue(), but also returns a handle that will "hold" the slot until released.
 	///
 	/// The hold will be released automatically once the hold handle is dropped.
+	///
+	/// Pools created via `new_gcra()` have no notion of a held slot, since permits are tracked as
+	/// a single arrival time rather than discrete slots; the returned handle is a no-op for them.
 	pub fn queue_with_hold(&self) -> impl Future<Output = HoldHandle> {
 		let inner = self.inner.clone();
 		async move {
 			// the "outer" loop which will only end via return
 			loop {
-				let now = Instant::now();
-				let mut sleep = inner.rate_duration;
-
-				for slot in &inner.slots {
-					if let Ok(mut slot) = slot.try_lock() {
-						// if the slot's instant is in the past
-						if slot.wait_until <= now {
-							// if the slot already has a hold receiver
-							if let Some(rx) = &mut slot.hold {
-								// if the hold has been released
-								if rx.try_recv().is_err() {
-									// the slot is expired/free
-									// set the slot's new expiry instant to be now + rate.duration
-									slot.wait_until = now + inner.rate_duration;
-
-									// clear the slot's hold receiver
-									slot.hold = None;
+				let sleep = match &inner.kind {
+					ThrottlePoolKind::Slotted(pool) => {
+						let now = Instant::now();
+						let mut sleep = pool.rate_duration;
+						let mut acquired = None;
+
+						for slot in &pool.slots {
+							if let Ok(mut slot) = slot.try_lock() {
+								// if the slot's instant is in the past
+								if slot.wait_until <= now {
+									// if the slot already has a hold receiver
+									if let Some(rx) = &mut slot.hold {
+										// if the hold has been released
+										if rx.try_recv().is_err() {
+											// the slot is expired/free
+											// set the slot's new expiry instant to be now + rate.duration
+											slot.wait_until = now + pool.rate_duration;
+
+											// clear the slot's hold receiver
+											slot.hold = None;
+										}
+										// else the hold is still in place
+										else {
+											// yield to the outer loop
+											sleep = Duration::from_secs(0);
+											break;
+										}
+									}
+									// else the slot does not have a hold receiver yet
+									else {
+										// set the slot's hold receiver
+
+										let (tx, rx) = futures::channel::oneshot::channel();
+										slot.hold = Some(rx);
+
+										// let the stream end
+										acquired = Some(HoldHandle { tx: Some(tx) });
+										break;
+									}
 								}
-								// else the hold is still in place
+								// else the slot's expiry is in the future
 								else {
-									// yield to the outer loop
-									sleep = Duration::from_secs(0);
-									break;
+									// if the slot's expiry is the earliest one we've encountered, use it
+									sleep = std::cmp::min(slot.wait_until - now, sleep);
 								}
 							}
-							// else the slot does not have a hold receiver yet
+							// else we couldn't lock the mutex
 							else {
-								// set the slot's hold receiver
-
-								let (tx, rx) = futures::channel::oneshot::channel();
-								slot.hold = Some(rx);
-
-								// let the stream end
-								return HoldHandle { tx: Some(tx) };
+								// yield to the outer loop
+								sleep = Duration::from_secs(0);
+								break;
 							}
 						}
-						// else the slot's expiry is in the future
-						else {
-							// if the slot's expiry is the earliest one we've encountered, use it
-							sleep = std::cmp::min(slot.wait_until - now, sleep);
+
+						if let Some(handle) = acquired {
+							return handle;
 						}
+
+						sleep
 					}
-					// else we couldn't lock the mutex
-					else {
-						// yield to the outer loop
-						sleep = Duration::from_secs(0);
-						break;
+					ThrottlePoolKind::Gcra(pool) => {
+						let now = Instant::now();
+						let mut tat = pool.tat.lock().expect("poisoned mutex");
+						let theoretical_arrival = std::cmp::max(*tat, now);
+
+						// if the theoretical arrival time is within our burst tolerance, allow it
+						// now, and advance the theoretical arrival time by one emission interval
+						if theoretical_arrival - now <= pool.tau {
+							*tat = theoretical_arrival + pool.emission_interval;
+
+							return HoldHandle { tx: None };
+						}
+
+						// otherwise, the caller must wait until we're back within tolerance
+						theoretical_arrival - pool.tau - now
 					}
-				}
+				};
+
+				let sleep = match &inner.jitter {
+					Some(jitter) => jitter.apply(sleep),
+					None => sleep,
+				};
 
 				if log_enabled!(log::Level::Trace) {
 					trace!("Sleeping for {:?}", sleep);
@@ -113,6 +218,39 @@ impl ThrottlePool {
 
 #[derive(Debug)]
 struct ThrottlePoolInner {
+	kind: ThrottlePoolKind,
+	jitter: Option<Jitter>,
+}
+
+#[derive(Debug)]
+enum ThrottlePoolKind {
+	Slotted(SlottedPool),
+	Gcra(GcraPool),
+}
+
+/// Configures random jitter added to the delays computed by a `ThrottlePool`.
+///
+/// See `ThrottlePool::with_jitter()`.
+#[derive(Copy, Clone, Debug)]
+struct Jitter {
+	min: Duration,
+	interval: Duration,
+}
+
+impl Jitter {
+	fn apply(&self, sleep: Duration) -> Duration {
+		let extra_nanos = if self.interval.is_zero() {
+			0
+		} else {
+			rand::thread_rng().gen_range(0..=self.interval.as_nanos())
+		};
+
+		sleep + self.min + Duration::from_nanos(extra_nanos as u64)
+	}
+}
+
+#[derive(Debug)]
+struct SlottedPool {
 	rate_duration: Duration,
 	slots: Vec<Mutex<Slot>>, // expiry times, one for each item in rate.count
 }
@@ -123,6 +261,13 @@ struct Slot {
 	hold: Option<Receiver<()>>,
 }
 
+#[derive(Debug)]
+struct GcraPool {
+	emission_interval: Duration,
+	tau: Duration,
+	tat: Mutex<Instant>, // theoretical arrival time
+}
+
 pub struct HoldHandle {
 	// when the QueueHandle is dropped, so is its tx, which notifies the rx of cancellation
 	tx: Option<Sender<()>>,
@@ -0,0 +1,115 @@
+use super::ThrottlePool;
+use futures::task::{Context, Poll};
+use futures::{ready, Future, FutureExt, Sink};
+use pin_utils::{unsafe_pinned, unsafe_unpinned};
+use std::marker::PhantomData;
+use std::pin::Pin;
+
+/// Provides a `throttle_sink()` method on all `Sink`'s.
+pub trait ThrottledSinkExt<Item> {
+	/// Returns a new sink, which throttles items sent into the original sink, according to the
+	/// rate defined by `pool`.
+	fn throttle_sink(self, pool: ThrottlePool) -> ThrottledSink<Self, Item>
+	where
+		Self: Sink<Item> + Sized,
+	{
+		ThrottledSink {
+			sink_pinned: self,
+			pool,
+			state_unpinned: State::None,
+			slot_pinned: None,
+			item_phantom: PhantomData,
+		}
+	}
+}
+
+impl<T, Item> ThrottledSinkExt<Item> for T where T: Sink<Item> {}
+
+/// A sink combinator which throttles the items sent into it, via a shared `ThrottlePool`.
+///
+/// This structure is produced by the `ThrottledSinkExt::throttle_sink()` method. Since a
+/// `ThrottlePool` can also be shared with a `Throttled` stream (via `ThrottledStream::throttle()`),
+/// a single pool can be used to throttle a `Stream` and a `Sink` together as one group.
+#[must_use = "sinks do nothing unless polled"]
+pub struct ThrottledSink<S, Item>
+where
+	S: Sink<Item> + 'static,
+{
+	sink_pinned: S,
+	pool: ThrottlePool,
+	state_unpinned: State,
+	slot_pinned: Option<Pin<Box<dyn Future<Output = ()> + Send + 'static>>>,
+	item_phantom: PhantomData<Item>,
+}
+
+impl<S, Item> ThrottledSink<S, Item>
+where
+	S: Sink<Item> + 'static,
+{
+	unsafe_pinned!(sink_pinned: S);
+	unsafe_unpinned!(state_unpinned: State);
+	unsafe_pinned!(slot_pinned: Option<Pin<Box<dyn Future<Output = ()> + Send + 'static>>>);
+}
+
+impl<S, Item> Sink<Item> for ThrottledSink<S, Item>
+where
+	S: Sink<Item>,
+{
+	type Error = S::Error;
+
+	/// Calls ThrottlePool::queue() to get a slot in the throttle queue, waits for it to resolve,
+	/// and only then delegates to the inner sink's poll_ready().
+	fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		if let State::None = self.state_unpinned {
+			// get a slot future from the pool, and store it
+			let slot = self.pool.queue().boxed();
+			self.as_mut().slot_pinned().set(Some(slot));
+
+			*self.as_mut().state_unpinned() = State::Slot;
+		}
+
+		if let State::Slot = self.state_unpinned {
+			// poll the slot future
+			let _ = ready!(self
+				.as_mut()
+				.slot_pinned()
+				.as_pin_mut()
+				.expect("impossible: slot future was None, during State::Slot")
+				.poll(cx));
+
+			// clear the slot future, now that it has finished
+			self.as_mut().slot_pinned().set(None);
+
+			*self.as_mut().state_unpinned() = State::Ready;
+		}
+
+		// State::Ready: the throttle slot is acquired, so defer to the inner sink
+		self.as_mut().sink_pinned().poll_ready(cx)
+	}
+
+	fn start_send(mut self: Pin<&mut Self>, item: Item) -> Result<(), Self::Error> {
+		// reset the state, so the next item waits for a new throttle slot
+		*self.as_mut().state_unpinned() = State::None;
+
+		self.as_mut().sink_pinned().start_send(item)
+	}
+
+	fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		self.as_mut().sink_pinned().poll_flush(cx)
+	}
+
+	fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		self.as_mut().sink_pinned().poll_close(cx)
+	}
+}
+
+enum State {
+	// no throttle slot has been requested yet for the item currently being sent
+	None,
+
+	// we are polling the internal ThrottlePool::queue() slot Future
+	Slot,
+
+	// the throttle slot has been acquired; poll_ready() now defers to the inner sink
+	Ready,
+}
@@ -1,7 +1,8 @@
-use super::ThrottlePool;
+use super::{KeyedThrottlePool, ThrottlePool};
 use futures::task::{Context, Poll};
 use futures::{ready, Future, FutureExt, Stream};
 use pin_utils::{unsafe_pinned, unsafe_unpinned};
+use std::hash::Hash;
 use std::pin::Pin;
 
 /// Provides a `throttle()` method on all `Stream`'s.
@@ -19,6 +20,23 @@ pub trait ThrottledStream {
 			slot_pinned: None,
 		}
 	}
+
+	/// Returns a new stream, which throttles items from the original stream according to `pool`,
+	/// where `key_fn` picks which of `pool`'s per-key rate limits applies to each item.
+	fn throttle_keyed<K, F>(self, pool: KeyedThrottlePool<K>, key_fn: F) -> ThrottledKeyed<Self, K, F>
+	where
+		Self: Stream + Sized,
+		K: Hash + Eq + Clone + 'static,
+		F: Fn(&Self::Item) -> K,
+	{
+		ThrottledKeyed {
+			stream_pinned: self,
+			pool,
+			key_fn,
+			state_unpinned: KeyedState::None,
+			slot_pinned: None,
+		}
+	}
 }
 
 impl<T: Stream> ThrottledStream for T {}
@@ -113,3 +131,101 @@ enum State {
 	// the internal stream has ended, nothing more to do
 	Done,
 }
+
+/// A stream combinator which throttles its elements via a shared `KeyedThrottlePool`, keying each
+/// item with a caller-provided function.
+///
+/// This structure is produced by the `ThrottledStream::throttle_keyed()` method.
+#[must_use = "streams do nothing unless polled"]
+pub struct ThrottledKeyed<S, K, F>
+where
+	S: Stream + 'static,
+{
+	stream_pinned: S,
+	pool: KeyedThrottlePool<K>,
+	key_fn: F,
+	state_unpinned: KeyedState<S::Item>,
+	slot_pinned: Option<Pin<Box<dyn Future<Output = ()> + Send + 'static>>>,
+}
+
+impl<S, K, F> ThrottledKeyed<S, K, F>
+where
+	S: Stream + 'static,
+{
+	unsafe_pinned!(stream_pinned: S);
+	unsafe_unpinned!(state_unpinned: KeyedState<S::Item>);
+	unsafe_pinned!(slot_pinned: Option<Pin<Box<dyn Future<Output = ()> + Send + 'static>>>);
+}
+
+impl<S, K, F> Stream for ThrottledKeyed<S, K, F>
+where
+	S: Stream,
+	K: Hash + Eq + Clone + 'static,
+	F: Fn(&S::Item) -> K,
+{
+	type Item = S::Item;
+
+	/// Polls the underlying stream for an item, determines its key via `key_fn`, then calls
+	/// `KeyedThrottlePool::queue()` for that key, waits for it to resolve, and produces the item.
+	fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		if let KeyedState::None = self.state_unpinned {
+			*self.as_mut().state_unpinned() = KeyedState::Stream;
+		}
+
+		if let KeyedState::Stream = self.state_unpinned {
+			// if polling the internal stream produced an item
+			if let Some(item) = ready!(self.as_mut().stream_pinned().poll_next(cx)) {
+				// get a slot future from the pool, keyed by this item, and store it
+				let key = (self.key_fn)(&item);
+				let slot = self.pool.queue(key).boxed();
+				self.as_mut().slot_pinned().set(Some(slot));
+
+				*self.as_mut().state_unpinned() = KeyedState::Slot(Some(item));
+			}
+			// else the internal stream has ended
+			else {
+				// set the state to Done, from which it will never change again
+				*self.as_mut().state_unpinned() = KeyedState::Done;
+			}
+		}
+
+		if let KeyedState::Slot(_) = self.state_unpinned {
+			// poll the slot future
+			let _ = ready!(self
+				.as_mut()
+				.slot_pinned()
+				.as_pin_mut()
+				.expect("impossible: slot future was None, during KeyedState::Slot")
+				.poll(cx));
+
+			// clear the slot future, now that it has finished
+			self.as_mut().slot_pinned().set(None);
+
+			let item = match std::mem::replace(self.as_mut().state_unpinned(), KeyedState::None) {
+				KeyedState::Slot(item) => {
+					item.expect("impossible: item was None, during KeyedState::Slot")
+				}
+				_ => unreachable!(),
+			};
+
+			// return the item from the internal stream
+			return Poll::Ready(Some(item));
+		}
+
+		Poll::Ready(None)
+	}
+}
+
+enum KeyedState<Item> {
+	// the stream has not been polled yet, or in the previous poll returned an item
+	None,
+
+	// we are polling the internal Stream
+	Stream,
+
+	// we are polling the internal KeyedThrottlePool::queue() slot Future, for the held item
+	Slot(Option<Item>),
+
+	// the internal stream has ended, nothing more to do
+	Done,
+}